@@ -0,0 +1,214 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+/// A Digital Elevation Model: a regular grid of elevation samples plus the
+/// georeferencing metadata needed to place it in world space.
+///
+/// This replaces the old pattern of passing `(Vec<f32>, usize, usize)` around
+/// and hard-coding `-99999.0` as the NODATA sentinel everywhere; the real
+/// NODATA value and the `xllcorner`/`yllcorner`/`cellsize` header fields are
+/// now carried alongside the data instead of being parsed and then discarded.
+pub struct Dem {
+    pub width: usize,
+    pub height: usize,
+    pub cell_size: f32,
+    pub xllcorner: f32,
+    pub yllcorner: f32,
+    pub nodata: f32,
+    pub elevations: Vec<f32>,
+    /// Whether `xllcorner`/`yllcorner`/`cell_size` came from the source file
+    /// rather than being filled in as defaults (GridFloat headers may omit
+    /// them). Callers that emit world coordinates (e.g. a world file) should
+    /// check this before trusting those fields.
+    pub has_georeference: bool,
+}
+
+impl Dem {
+    /// Reads an ESRI ASCII grid (`.asc`) file.
+    pub fn from_asc(path: &str) -> anyhow::Result<Dem> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let mut lines = reader.lines();
+
+        let width: usize = lines.next().unwrap()?.split_whitespace().last().unwrap().parse()?;
+        let height: usize = lines.next().unwrap()?.split_whitespace().last().unwrap().parse()?;
+        let xllcorner: f32 = lines.next().unwrap()?.split_whitespace().last().unwrap().parse()?;
+        let yllcorner: f32 = lines.next().unwrap()?.split_whitespace().last().unwrap().parse()?;
+        let cell_size: f32 = lines.next().unwrap()?.split_whitespace().last().unwrap().parse()?;
+        let nodata: f32 = lines.next().unwrap()?.split_whitespace().last().unwrap().parse()?;
+
+        let mut elevations = Vec::with_capacity(width * height);
+        for line in lines {
+            for val in line?.split_whitespace() {
+                let v: f32 = val.parse().unwrap_or(nodata);
+                elevations.push(v);
+            }
+        }
+
+        Ok(Dem {
+            width,
+            height,
+            cell_size,
+            xllcorner,
+            yllcorner,
+            nodata,
+            elevations,
+            has_georeference: true,
+        })
+    }
+
+    /// Reads a USGS GridFloat dataset (a `.hdr` text header plus a companion
+    /// `.flt` binary file of row-major 32-bit floats). `path` may point at
+    /// either half of the pair; the sibling file is located by swapping the
+    /// extension.
+    pub fn from_gridfloat(path: &str) -> anyhow::Result<Dem> {
+        let hdr_path = Path::new(path).with_extension("hdr");
+        let flt_path = Path::new(path).with_extension("flt");
+
+        let hdr_file = File::open(&hdr_path)?;
+        let reader = BufReader::new(hdr_file);
+        let mut width: Option<usize> = None;
+        let mut height: Option<usize> = None;
+        let mut xllcorner = 0.0_f32;
+        let mut yllcorner = 0.0_f32;
+        let mut cell_size = 1.0_f32;
+        let mut nodata = -99999.0_f32;
+        let mut little_endian = true;
+        let (mut has_xllcorner, mut has_yllcorner, mut has_cellsize) = (false, false, false);
+
+        for line in reader.lines() {
+            let line = line?;
+            let mut parts = line.split_whitespace();
+            let key = match parts.next() {
+                Some(k) => k.to_ascii_lowercase(),
+                None => continue,
+            };
+            let value = match parts.next() {
+                Some(v) => v,
+                None => continue,
+            };
+
+            match key.as_str() {
+                "ncols" => width = Some(value.parse()?),
+                "nrows" => height = Some(value.parse()?),
+                "xllcorner" => {
+                    xllcorner = value.parse()?;
+                    has_xllcorner = true;
+                }
+                "yllcorner" => {
+                    yllcorner = value.parse()?;
+                    has_yllcorner = true;
+                }
+                "cellsize" => {
+                    cell_size = value.parse()?;
+                    has_cellsize = true;
+                }
+                "nodata_value" => nodata = value.parse()?,
+                "byteorder" => little_endian = value.eq_ignore_ascii_case("LSBFIRST"),
+                _ => {}
+            }
+        }
+        let has_georeference = has_xllcorner && has_yllcorner && has_cellsize;
+
+        let width = width.ok_or_else(|| anyhow::anyhow!("GridFloat header missing ncols"))?;
+        let height = height.ok_or_else(|| anyhow::anyhow!("GridFloat header missing nrows"))?;
+
+        let mut flt_file = File::open(&flt_path)?;
+        let mut bytes = Vec::with_capacity(width * height * 4);
+        flt_file.read_to_end(&mut bytes)?;
+
+        let expected_len = width * height;
+        if bytes.len() != expected_len * 4 {
+            anyhow::bail!(
+                "GridFloat {:?} has {} bytes, expected {} ({ncols}x{nrows} f32 cells)",
+                flt_path,
+                bytes.len(),
+                expected_len * 4,
+                ncols = width,
+                nrows = height,
+            );
+        }
+
+        let mut elevations = Vec::with_capacity(expected_len);
+        for chunk in bytes.chunks_exact(4) {
+            let raw: [u8; 4] = chunk.try_into().unwrap();
+            let v = if little_endian {
+                f32::from_le_bytes(raw)
+            } else {
+                f32::from_be_bytes(raw)
+            };
+            elevations.push(v);
+        }
+
+        Ok(Dem {
+            width,
+            height,
+            cell_size,
+            xllcorner,
+            yllcorner,
+            nodata,
+            elevations,
+            has_georeference,
+        })
+    }
+
+    /// Reads either format based on the file extension (`.flt`/`.hdr` for
+    /// GridFloat, anything else as ESRI ASCII grid).
+    pub fn from_path(path: &str) -> anyhow::Result<Dem> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match extension.as_str() {
+            "flt" | "hdr" => Dem::from_gridfloat(path),
+            _ => Dem::from_asc(path),
+        }
+    }
+
+    /// Returns the `(min, max)` elevation across all cells that are not
+    /// NODATA, skipping the file-declared `nodata` value rather than a
+    /// hard-coded magic number.
+    pub fn min_max(&self) -> (f32, f32) {
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        for &v in &self.elevations {
+            if v == self.nodata {
+                continue;
+            }
+            min = min.min(v);
+            max = max.max(v);
+        }
+        (min, max)
+    }
+
+    /// Returns the elevation at the given column/row, or `None` if the cell
+    /// is NODATA or out of bounds.
+    pub fn elevation_at(&self, col: usize, row: usize) -> Option<f32> {
+        if col >= self.width || row >= self.height {
+            return None;
+        }
+        let v = self.elevations[row * self.width + col];
+        if v == self.nodata {
+            None
+        } else {
+            Some(v)
+        }
+    }
+
+    /// Converts a grid column/row into the world-space coordinate of that
+    /// cell's *center*, using the same convention as the world file written
+    /// alongside `--output`: row 0 is the north (top) row, but `yllcorner` is
+    /// the *south* edge, so row has to count down from the top rather than
+    /// up from `yllcorner`.
+    pub fn world_xy(&self, col: usize, row: usize) -> (f32, f32) {
+        (
+            self.xllcorner + (col as f32 + 0.5) * self.cell_size,
+            self.yllcorner + (self.height as f32 - 0.5 - row as f32) * self.cell_size,
+        )
+    }
+}
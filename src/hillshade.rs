@@ -0,0 +1,146 @@
+use crate::dem::Dem;
+
+/// Parameters controlling how a hillshade is rendered.
+pub struct HillshadeParams {
+    pub azimuth_deg: f32,
+    pub altitude_deg: f32,
+    pub z_factor: f32,
+    pub multidirectional: bool,
+}
+
+impl Default for HillshadeParams {
+    fn default() -> Self {
+        HillshadeParams {
+            azimuth_deg: 315.0,
+            altitude_deg: 45.0,
+            z_factor: 1.0,
+            multidirectional: false,
+        }
+    }
+}
+
+/// Azimuths averaged together in `--multidirectional` mode, chosen to spread
+/// the light sources around the compass and reduce the directional bias and
+/// flattened-valley artifacts of a single light source.
+const MULTIDIRECTIONAL_AZIMUTHS_DEG: [f32; 4] = [225.0, 270.0, 315.0, 360.0];
+
+/// Generates a hillshade image from the DEM using the standard Horn algorithm.
+pub fn generate_hillshade(dem: &Dem, params: &HillshadeParams) -> Vec<u8> {
+    let shade = if params.multidirectional {
+        let mut acc = vec![0.0f32; dem.width * dem.height];
+        for &azimuth_deg in &MULTIDIRECTIONAL_AZIMUTHS_DEG {
+            let pass = hillshade_pass(dem, azimuth_deg.to_radians(), params.altitude_deg.to_radians(), params.z_factor);
+            for (a, s) in acc.iter_mut().zip(pass) {
+                *a += s / MULTIDIRECTIONAL_AZIMUTHS_DEG.len() as f32;
+            }
+        }
+        acc
+    } else {
+        hillshade_pass(dem, params.azimuth_deg.to_radians(), params.altitude_deg.to_radians(), params.z_factor)
+    };
+
+    shade.iter().map(|&s| (s * 255.0).clamp(0.0, 255.0) as u8).collect()
+}
+
+/// Computes a single-direction Horn's-method hillshade, returning a shade
+/// fraction in `[0, 1]` per cell (NODATA cells are `0.0`).
+fn hillshade_pass(dem: &Dem, azimuth: f32, altitude: f32, z_factor: f32) -> Vec<f32> {
+    let (width, height) = (dem.width, dem.height);
+    let mut shade = vec![0.0f32; width * height];
+    let zenith = std::f32::consts::FRAC_PI_2 - altitude;
+
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let center_idx = y * width + x;
+
+            if dem.elevations[center_idx] == dem.nodata {
+                continue;
+            }
+
+            // Helper function to get the DEM value at a specific offset.
+            let get = |dx: isize, dy: isize| {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    return dem.nodata;
+                }
+                let i = ny as usize * width + nx as usize;
+                dem.elevations[i]
+            };
+
+            // 3x3 Sobel-weighted slope in x and y.
+            let dzdx = ((get(1, -1) + 2.0 * get(1, 0) + get(1, 1)) -
+                        (get(-1, -1) + 2.0 * get(-1, 0) + get(-1, 1))) / (8.0 * dem.cell_size);
+            let dzdy = ((get(-1, 1) + 2.0 * get(0, 1) + get(1, 1)) -
+                        (get(-1, -1) + 2.0 * get(0, -1) + get(1, -1))) / (8.0 * dem.cell_size);
+
+            let slope = (z_factor * (dzdx * dzdx + dzdy * dzdy).sqrt()).atan();
+            let mut aspect = dzdy.atan2(-dzdx);
+            if aspect < 0.0 {
+                aspect += 2.0 * std::f32::consts::PI;
+            }
+
+            let s = zenith.cos() * slope.cos() + zenith.sin() * slope.sin() * (azimuth - aspect).cos();
+            shade[center_idx] = s.max(0.0);
+        }
+    }
+
+    shade
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A DEM whose elevation is `a*col + b*row`, so the Sobel-derived
+    /// `dzdx`/`dzdy` reduce exactly to `a / cell_size` and `b / cell_size`.
+    fn linear_dem(a: f32, b: f32, cell_size: f32) -> Dem {
+        let (width, height) = (3, 3);
+        let mut elevations = Vec::with_capacity(width * height);
+        for row in 0..height {
+            for col in 0..width {
+                elevations.push(a * col as f32 + b * row as f32);
+            }
+        }
+        Dem {
+            width,
+            height,
+            cell_size,
+            xllcorner: 0.0,
+            yllcorner: 0.0,
+            nodata: -99999.0,
+            elevations,
+            has_georeference: true,
+        }
+    }
+
+    #[test]
+    fn flat_terrain_shades_by_cos_zenith_only() {
+        let dem = linear_dem(0.0, 0.0, 1.0);
+        let altitude = 45.0_f32.to_radians();
+        let shade = hillshade_pass(&dem, 315.0_f32.to_radians(), altitude, 1.0);
+
+        let center = shade[dem.width + 1];
+        let expected = (std::f32::consts::FRAC_PI_2 - altitude).cos();
+        assert!((center - expected).abs() < 1e-4, "center={center} expected={expected}");
+    }
+
+    #[test]
+    fn tilted_plane_matches_horns_formula() {
+        let (a, b, cell_size) = (5.0_f32, 3.0_f32, 2.0_f32);
+        let dem = linear_dem(a, b, cell_size);
+        let (azimuth, altitude, z_factor) = (315.0_f32.to_radians(), 45.0_f32.to_radians(), 1.0_f32);
+        let shade = hillshade_pass(&dem, azimuth, altitude, z_factor);
+
+        let dzdx = a / cell_size;
+        let dzdy = b / cell_size;
+        let slope = (z_factor * (dzdx * dzdx + dzdy * dzdy).sqrt()).atan();
+        let aspect = dzdy.atan2(-dzdx);
+        let zenith = std::f32::consts::FRAC_PI_2 - altitude;
+        let expected =
+            (zenith.cos() * slope.cos() + zenith.sin() * slope.sin() * (azimuth - aspect).cos()).max(0.0);
+
+        let center = shade[dem.width + 1];
+        assert!((center - expected).abs() < 1e-4, "center={center} expected={expected}");
+    }
+}
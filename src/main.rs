@@ -1,7 +1,17 @@
+mod dem;
+mod hillshade;
+mod mesh;
+mod output;
+mod palette;
+
 use anyhow::Result;
 use clap::Parser;
-use show_image::{run_context, create_window, ImageInfo, ImageView, WindowOptions};
-use std::{fs::File, io::{BufRead, BufReader}};
+use dem::Dem;
+use hillshade::{generate_hillshade, HillshadeParams};
+use mesh::{Heightmap, Vec3};
+use output::write_output;
+use palette::Palette;
+use show_image::{run_context, create_window, event::WindowEvent, ImageInfo, ImageView, WindowOptions};
 
 // This program visualizes Digital Elevation Model (DEM) data from .asc files.
 // It supports various visualization modes including grayscale, color, hillshade, and a combination of color and hillshade.
@@ -17,107 +27,158 @@ use std::{fs::File, io::{BufRead, BufReader}};
 // This struct defines the command-line arguments for the DEM Viewer.
 #[derive(Parser)]
 struct Args {
-    // Path to the .asc DEM file to be visualized.
+    // Path to the DEM file to be visualized (.asc, or either half of a GridFloat .flt/.hdr pair).
     input_file: String,
 
     // Mode of visualization: grayscale, color, hillshade, or color+hillshade.
     #[clap(long, default_value = "grayscale")]
     mode: String,
+
+    // Direction the simulated light comes from, in degrees clockwise from north.
+    #[clap(long, default_value_t = 315.0)]
+    azimuth: f32,
+
+    // Angle of the simulated light above the horizon, in degrees.
+    #[clap(long, default_value_t = 45.0)]
+    altitude: f32,
+
+    // Vertical exaggeration applied to the slope before shading.
+    #[clap(long = "z-factor", default_value_t = 1.0)]
+    z_factor: f32,
+
+    // Average hillshades from four azimuths (225/270/315/360 degrees) instead
+    // of a single light source, reducing directional bias in flat valleys.
+    #[clap(long)]
+    multidirectional: bool,
+
+    // Render straight to this file and exit instead of opening a window.
+    // Useful for scripts and CI generating thumbnails over many tiles.
+    #[clap(long)]
+    output: Option<String>,
+
+    // Color ramp for "color" and "color+hillshade" modes: a built-in name
+    // ("hypsometric") or a path to a custom elevation-stops file.
+    #[clap(long, default_value = "hypsometric")]
+    palette: String,
+
+    // Elevations at or below this are rendered as a fixed water color,
+    // regardless of where they fall in the palette's stops.
+    #[clap(long = "sea-level", default_value_t = 0.0)]
+    sea_level: f32,
+}
+
+/// A rendered visualization, still tagged with its pixel format so callers
+/// can build the right kind of window `ImageView` or on-disk image encoder.
+enum RenderedImage {
+    Gray(Vec<u8>),
+    Rgb(Vec<u8>),
+}
+
+/// Renders the DEM in the requested mode.
+fn render(dem: &Dem, mode: &str, hillshade_params: &HillshadeParams, palette: &Palette) -> anyhow::Result<RenderedImage> {
+    match mode {
+        "grayscale" => Ok(RenderedImage::Gray(dem_to_grayscale(dem)?)),
+        "hillshade" => Ok(RenderedImage::Gray(generate_hillshade(dem, hillshade_params))),
+        "color" => Ok(RenderedImage::Rgb(dem_to_color_image(dem, palette))),
+        "color+hillshade" => {
+            let color = dem_to_color_image(dem, palette);
+            let hill = generate_hillshade(dem, hillshade_params);
+            Ok(RenderedImage::Rgb(blend_with_hillshade(&color, &hill)))
+        }
+        _ => anyhow::bail!("Unknown mode. Use grayscale, color, hillshade, or color+hillshade"),
+    }
 }
 
 fn main() -> Result<()> {
+    // Parse the command-line arguments.
+    let args = Args::parse();
+
+    // Read the DEM data from the specified input file, dispatching on its extension
+    // so both ESRI ASCII grids and USGS GridFloat tiles can be viewed.
+    let dem = Dem::from_path(&args.input_file)?;
+    let (ncols, nrows) = (dem.width, dem.height);
+    let hillshade_params = HillshadeParams {
+        azimuth_deg: args.azimuth,
+        altitude_deg: args.altitude,
+        z_factor: args.z_factor,
+        multidirectional: args.multidirectional,
+    };
+    let palette = Palette::resolve(&args.palette, args.sea_level)?;
+
+    let rendered = render(&dem, &args.mode, &hillshade_params, &palette)?;
+
+    // In batch mode, write the rendered image (plus a world file) straight to
+    // disk and exit without ever opening a window.
+    if let Some(output_path) = &args.output {
+        write_output(&dem, &rendered, output_path)?;
+        return Ok(());
+    }
+
     // Running the application within a context that manages the image display type.
     run_context(move || {
-        // Parse the command-line arguments.
-        let args = Args::parse();
-
-        // Read the DEM data from the specified input file.
-        let (dem, ncols, nrows) = read_asc_file(&args.input_file)?;
-        // detect the visualization mode from the command line arguments.
-        let mode = args.mode;
-
-        // Create an image view based on the selected visualization mode.
-        let image_view: ImageView<'static> = match mode.as_str() {
-            "grayscale" => {
-                // Convert the DEM data to a grayscale image.
-                let grayscale = dem_to_grayscale(&dem)?.into_boxed_slice();
-                let leaked = Box::leak(grayscale);
-                ImageView::new(ImageInfo::mono8(ncols as u32, nrows as u32), leaked)
-            }
-            "hillshade" => {
-                // Generate a hillshade image from the DEM data.
-                let hill = generate_hillshade(&dem, ncols, nrows).into_boxed_slice();
-                let leaked = Box::leak(hill);
+        // Create an image view based on the rendered visualization.
+        let image_view: ImageView<'static> = match rendered {
+            RenderedImage::Gray(data) => {
+                let leaked = Box::leak(data.into_boxed_slice());
                 ImageView::new(ImageInfo::mono8(ncols as u32, nrows as u32), leaked)
             }
-            "color" => {
-                // Convert the DEM data to a color image.
-                let color = dem_to_color_image(&dem, ncols, nrows)?.into_boxed_slice();
-                let leaked = Box::leak(color);
-                ImageView::new(ImageInfo::rgb8(ncols as u32, nrows as u32), leaked)
-            }
-            "color+hillshade" => {
-                // Blend the color image with the hillshade.
-                let color = dem_to_color_image(&dem, ncols, nrows)?;
-                let hill = generate_hillshade(&dem, ncols, nrows);
-                let blended = blend_with_hillshade(&color, &hill).into_boxed_slice();
-                let leaked = Box::leak(blended);
+            RenderedImage::Rgb(data) => {
+                let leaked = Box::leak(data.into_boxed_slice());
                 ImageView::new(ImageInfo::rgb8(ncols as u32, nrows as u32), leaked)
             }
-            _ => panic!("Unknown mode. Use grayscale, color, hillshade, or color+hillshade"),
         };
 
         // Create a window to display the image.
         let window = create_window("DEM Viewer", WindowOptions::default())?;
         window.set_image("dem", image_view)?;
+
+        // Build the mesh once so hovering over the window can report the
+        // terrain elevation and world coordinate under the cursor. Mesh
+        // vertices are already in world space (see `Dem::world_xy`), so the
+        // ray origin needs to be too.
+        let heightmap = Heightmap::build(&dem);
+        let events = window.event_channel()?;
+        std::thread::spawn(move || {
+            for event in events {
+                let WindowEvent::MouseMove(mouse_event) = event else {
+                    continue;
+                };
+
+                // The window displays the DEM grid 1:1, so the cursor's pixel
+                // position is directly the (col, row) to probe. Cast a ray
+                // straight down through the mesh from high above that point,
+                // going through `Dem::world_xy` so this agrees with the
+                // `.pgw`/`.wld` file `--output` would write for the same DEM.
+                let col = mouse_event.position.x as usize;
+                let row = mouse_event.position.y as usize;
+                let (x, z) = dem.world_xy(col, row);
+                let origin = Vec3::new(x, 1.0e6, z);
+                let direction = Vec3::new(0.0, -1.0, 0.0);
+
+                if let Some((world, elevation)) = heightmap.raycast(origin, direction) {
+                    println!("elevation {elevation:.1} m at world ({:.1}, {:.1})", world.x, world.z);
+                }
+            }
+        });
+
         // Keep the window open.
         std::thread::park();
         Ok::<(), anyhow::Error>(())
     })
 }
 
-/// Reads the .asc file and returns the DEM data along with the number of columns and rows.
-fn read_asc_file(path: &str) -> anyhow::Result<(Vec<f32>, usize, usize)> {
-    // Open the file and create a buffered reader.
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines();
-
-    // Parse the header information from the file.
-    let ncols: usize = lines.next().unwrap()?.split_whitespace().last().unwrap().parse()?;
-    let nrows: usize = lines.next().unwrap()?.split_whitespace().last().unwrap().parse()?;
-    lines.next(); // Skip xllcorner
-    lines.next(); // Skip yllcorner
-    lines.next(); // Skip cellsize
-    let nodata_value: f32 = lines.next().unwrap()?.split_whitespace().last().unwrap().parse()?;
-
-    // Read the DEM data into a vector.
-    let mut data = Vec::with_capacity(ncols * nrows);
-    for line in lines {
-        for val in line?.split_whitespace() {
-            let v: f32 = val.parse().unwrap_or(nodata_value);
-            data.push(v);
-        }
-    }
-
-    Ok((data, ncols, nrows))
-}
-
 /// Converts the DEM data to a grayscale image.
-fn dem_to_grayscale(dem: &[f32]) -> anyhow::Result<Vec<u8>> {
-    let nodata_value = -99999.0;
-
-    // Filter out the no-data values and find the min and max elevations.
-    let valid: Vec<f32> = dem.iter().copied().filter(|&v| v != nodata_value).collect();
-    let min = valid.iter().cloned().fold(f32::INFINITY, f32::min);
-    let max = valid.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+fn dem_to_grayscale(dem: &Dem) -> anyhow::Result<Vec<u8>> {
+    // Find the min and max elevations, skipping the file-declared NODATA value.
+    let (min, max) = dem.min_max();
     let scale = 255.0 / (max - min);
 
     // Convert the DEM values to grayscale values.
     let image: Vec<u8> = dem
+        .elevations
         .iter()
         .map(|&v| {
-            if v == nodata_value {
+            if v == dem.nodata {
                 0
             } else {
                 ((v - min) * scale).clamp(0.0, 255.0) as u8
@@ -128,84 +189,21 @@ fn dem_to_grayscale(dem: &[f32]) -> anyhow::Result<Vec<u8>> {
     Ok(image)
 }
 
-/// Converts the DEM data to a color image using a color gradient.
-fn dem_to_color_image(dem: &[f32], width: usize, height: usize) -> anyhow::Result<Vec<u8>> {
-    let nodata = -99999.0;
-
-    // Filter out the no-data values and find the min and max elevations.
-    let valid: Vec<f32> = dem.iter().copied().filter(|&v| v != nodata).collect();
-    let min = valid.iter().cloned().fold(f32::INFINITY, f32::min);
-    let max = valid.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+/// Converts the DEM data to a color image using a hypsometric tint palette.
+fn dem_to_color_image(dem: &Dem, palette: &Palette) -> Vec<u8> {
+    let mut rgb_image = Vec::with_capacity(dem.width * dem.height * 3);
 
-    // Create a color gradient.
-    let grad = colorgrad::turbo();
-    let mut rgb_image = Vec::with_capacity(width * height * 3);
-
-    // Convert the DEM values to RGB values using the color gradient.
-    for &v in dem {
-        if v == nodata {
+    // Convert the DEM values to RGB values using the palette's absolute
+    // elevation stops, so the color scale stays consistent across tiles.
+    for &v in &dem.elevations {
+        if v == dem.nodata {
             rgb_image.extend_from_slice(&[0, 0, 0]);
         } else {
-            let norm = (v - min) / (max - min);
-            let color = grad.at(norm as f64);
-            rgb_image.push((color.r * 255.0) as u8);
-            rgb_image.push((color.g * 255.0) as u8);
-            rgb_image.push((color.b * 255.0) as u8);
-        }
-    }
-
-    Ok(rgb_image)
-}
-
-/// Generates a hillshade image from the DEM data.
-fn generate_hillshade(dem: &[f32], width: usize, height: usize) -> Vec<u8> {
-    let mut image = vec![0u8; width * height];
-    let scale = 1.0;
-    let azimuth = 315.0_f32.to_radians();
-    let altitude = 45.0_f32.to_radians();
-    let nodata = -99999.0;
-
-    // Iterate over each pixel in the DEM data to calculate the hillshade.
-    for y in 1..height - 1 {
-        for x in 1..width - 1 {
-            let center_idx = y * width + x;
-
-            // Helper function to get the DEM value at a specific offset.
-            let get = |dx: isize, dy: isize| {
-                let nx = x as isize + dx;
-                let ny = y as isize + dy;
-                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
-                    return nodata;
-                }
-                let i = ny as usize * width + nx as usize;
-                dem[i]
-            };
-
-            // Calculate the slope and aspect of the terrain.
-            let dzdx = ((get(1, -1) + 2.0 * get(1, 0) + get(1, 1)) -
-                        (get(-1, -1) + 2.0 * get(-1, 0) + get(-1, 1))) / (8.0 * scale);
-            let dzdy = ((get(-1, 1) + 2.0 * get(0, 1) + get(1, 1)) -
-                        (get(-1, -1) + 2.0 * get(0, -1) + get(1, -1))) / (8.0 * scale);
-
-            // Skip no-data values.
-            if dem[center_idx] == nodata {
-                image[center_idx] = 0;
-                continue;
-            }
-
-            let slope = (dzdx.powi(2) + dzdy.powi(2)).sqrt();
-            let aspect = dzdy.atan2(-dzdx);
-
-            // Calculate the hillshade value.
-            let shade = (altitude.sin() * (1.0 - slope.atan()).cos() +
-                         altitude.cos() * (1.0 - slope.atan()).sin() * (azimuth - aspect).cos())
-                         .max(0.0);
-
-            image[center_idx] = (shade * 255.0) as u8;
+            rgb_image.extend_from_slice(&palette.color_at(v));
         }
     }
 
-    image
+    rgb_image
 }
 
 /// Blends a color image with a hillshade image.
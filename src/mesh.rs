@@ -0,0 +1,363 @@
+use crate::dem::Dem;
+
+/// A point or direction in 3D world space: `x`/`z` are ground-plane
+/// coordinates (matching the DEM's `xllcorner`/`yllcorner` axes) and `y` is
+/// elevation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+
+    fn scale(self, s: f32) -> Vec3 {
+        Vec3::new(self.x * s, self.y * s, self.z * s)
+    }
+
+    fn dot(self, other: Vec3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn cross(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    fn min(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x.min(other.x), self.y.min(other.y), self.z.min(other.z))
+    }
+
+    fn max(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x.max(other.x), self.y.max(other.y), self.z.max(other.z))
+    }
+}
+
+/// A single mesh triangle.
+#[derive(Clone, Copy)]
+struct Triangle {
+    v0: Vec3,
+    v1: Vec3,
+    v2: Vec3,
+}
+
+fn centroid(tri: &Triangle) -> Vec3 {
+    tri.v0.add(tri.v1).add(tri.v2).scale(1.0 / 3.0)
+}
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy)]
+struct Aabb {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Aabb {
+    fn of_triangle(tri: &Triangle) -> Aabb {
+        Aabb {
+            min: tri.v0.min(tri.v1).min(tri.v2),
+            max: tri.v0.max(tri.v1).max(tri.v2),
+        }
+    }
+
+    fn union(self, other: Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Slab test. Returns the ray's entry/exit distances if it hits the box.
+    fn intersect(&self, origin: Vec3, inv_dir: Vec3) -> Option<(f32, f32)> {
+        let mut t_enter = f32::NEG_INFINITY;
+        let mut t_exit = f32::INFINITY;
+
+        for axis in 0..3 {
+            let (o, inv_d, lo, hi) = match axis {
+                0 => (origin.x, inv_dir.x, self.min.x, self.max.x),
+                1 => (origin.y, inv_dir.y, self.min.y, self.max.y),
+                _ => (origin.z, inv_dir.z, self.min.z, self.max.z),
+            };
+            let mut t0 = (lo - o) * inv_d;
+            let mut t1 = (hi - o) * inv_d;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_enter = t_enter.max(t0);
+            t_exit = t_exit.min(t1);
+        }
+
+        if t_enter <= t_exit {
+            Some((t_enter, t_exit))
+        } else {
+            None
+        }
+    }
+}
+
+/// A bounding-volume hierarchy node: either a leaf holding a few triangle
+/// indices, an internal node splitting its children's bounds, or `Empty`
+/// when the mesh has no triangles at all (e.g. a 1x1 DEM, or one whose
+/// NODATA cells wipe out every triangle).
+enum BvhNode {
+    Empty,
+    Leaf { bounds: Aabb, triangles: Vec<usize> },
+    Internal { bounds: Aabb, left: Box<BvhNode>, right: Box<BvhNode> },
+}
+
+impl BvhNode {
+    fn bounds(&self) -> Option<Aabb> {
+        match self {
+            BvhNode::Empty => None,
+            BvhNode::Leaf { bounds, .. } => Some(*bounds),
+            BvhNode::Internal { bounds, .. } => Some(*bounds),
+        }
+    }
+}
+
+/// Triangle count at or below which a BVH node stops splitting and becomes a leaf.
+const LEAF_SIZE: usize = 4;
+
+fn build_bvh(indices: &mut [usize], triangles: &[Triangle]) -> BvhNode {
+    if indices.is_empty() {
+        return BvhNode::Empty;
+    }
+
+    let bounds = indices
+        .iter()
+        .map(|&i| Aabb::of_triangle(&triangles[i]))
+        .reduce(Aabb::union)
+        .expect("checked non-empty above");
+
+    if indices.len() <= LEAF_SIZE {
+        return BvhNode::Leaf {
+            bounds,
+            triangles: indices.to_vec(),
+        };
+    }
+
+    // Split along the bounding box's longest axis at the median centroid.
+    let extent = bounds.max.sub(bounds.min);
+    let axis = if extent.x >= extent.y && extent.x >= extent.z {
+        0
+    } else if extent.y >= extent.z {
+        1
+    } else {
+        2
+    };
+
+    indices.sort_by(|&a, &b| {
+        let (ca, cb) = (centroid(&triangles[a]), centroid(&triangles[b]));
+        let (va, vb) = match axis {
+            0 => (ca.x, cb.x),
+            1 => (ca.y, cb.y),
+            _ => (ca.z, cb.z),
+        };
+        va.partial_cmp(&vb).unwrap()
+    });
+
+    let mid = indices.len() / 2;
+    let (left_indices, right_indices) = indices.split_at_mut(mid);
+    let left = build_bvh(left_indices, triangles);
+    let right = build_bvh(right_indices, triangles);
+
+    BvhNode::Internal {
+        bounds,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+/// Möller-Trumbore ray/triangle intersection. Returns the hit distance and
+/// world-space point.
+fn intersect_triangle(tri: &Triangle, origin: Vec3, dir: Vec3) -> Option<(f32, Vec3)> {
+    const EPSILON: f32 = 1e-6;
+
+    let edge1 = tri.v1.sub(tri.v0);
+    let edge2 = tri.v2.sub(tri.v0);
+    let h = dir.cross(edge2);
+    let a = edge1.dot(h);
+    if a.abs() < EPSILON {
+        return None; // Ray is parallel to the triangle's plane.
+    }
+
+    let f = 1.0 / a;
+    let s = origin.sub(tri.v0);
+    let u = f * s.dot(h);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let v = f * dir.dot(q);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = f * edge2.dot(q);
+    if t > EPSILON {
+        Some((t, origin.add(dir.scale(t))))
+    } else {
+        None
+    }
+}
+
+/// A triangle mesh built from a `Dem`'s heightfield, with a BVH over it so a
+/// camera ray can be intersected against the terrain in `O(log n)` rather
+/// than testing every triangle.
+pub struct Heightmap {
+    triangles: Vec<Triangle>,
+    root: BvhNode,
+}
+
+impl Heightmap {
+    /// Builds a mesh of two triangles per DEM cell (vertices at the cell's
+    /// world-space `(x, elevation, y)`, per `Dem::world_xy`), skipping any
+    /// triangle that touches a NODATA cell, then indexes it with a BVH.
+    pub fn build(dem: &Dem) -> Heightmap {
+        let mut triangles = Vec::new();
+
+        let vertex = |col: usize, row: usize| -> Option<Vec3> {
+            let elevation = dem.elevation_at(col, row)?;
+            let (x, z) = dem.world_xy(col, row);
+            Some(Vec3::new(x, elevation, z))
+        };
+
+        for row in 0..dem.height.saturating_sub(1) {
+            for col in 0..dem.width.saturating_sub(1) {
+                let (top_left, top_right, bottom_left, bottom_right) =
+                    (vertex(col, row), vertex(col + 1, row), vertex(col, row + 1), vertex(col + 1, row + 1));
+                let (Some(top_left), Some(top_right), Some(bottom_left), Some(bottom_right)) =
+                    (top_left, top_right, bottom_left, bottom_right)
+                else {
+                    continue;
+                };
+
+                triangles.push(Triangle { v0: top_left, v1: top_right, v2: bottom_right });
+                triangles.push(Triangle { v0: top_left, v1: bottom_right, v2: bottom_left });
+            }
+        }
+
+        let mut indices: Vec<usize> = (0..triangles.len()).collect();
+        let root = build_bvh(&mut indices, &triangles);
+
+        Heightmap { triangles, root }
+    }
+
+    /// Casts a ray through the mesh and returns the nearest hit's world
+    /// position together with its elevation (the hit's `y`).
+    pub fn raycast(&self, origin: Vec3, dir: Vec3) -> Option<(Vec3, f32)> {
+        let inv_dir = Vec3::new(1.0 / dir.x, 1.0 / dir.y, 1.0 / dir.z);
+        let mut closest: Option<(f32, Vec3)> = None;
+        self.raycast_node(&self.root, origin, dir, inv_dir, &mut closest);
+        closest.map(|(_, point)| (point, point.y))
+    }
+
+    fn raycast_node(
+        &self,
+        node: &BvhNode,
+        origin: Vec3,
+        dir: Vec3,
+        inv_dir: Vec3,
+        closest: &mut Option<(f32, Vec3)>,
+    ) {
+        let Some(bounds) = node.bounds() else {
+            return; // BvhNode::Empty: nothing to test.
+        };
+        let Some((t_enter, t_exit)) = bounds.intersect(origin, inv_dir) else {
+            return;
+        };
+        if t_exit < 0.0 {
+            return;
+        }
+        if let Some((best_t, _)) = *closest {
+            if t_enter > best_t {
+                return;
+            }
+        }
+
+        match node {
+            BvhNode::Empty => {}
+            BvhNode::Leaf { triangles: indices, .. } => {
+                for &i in indices {
+                    if let Some((t, point)) = intersect_triangle(&self.triangles[i], origin, dir) {
+                        if closest.is_none_or(|(best_t, _)| t < best_t) {
+                            *closest = Some((t, point));
+                        }
+                    }
+                }
+            }
+            BvhNode::Internal { left, right, .. } => {
+                self.raycast_node(left, origin, dir, inv_dir, closest);
+                self.raycast_node(right, origin, dir, inv_dir, closest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dem::Dem;
+
+    fn flat_dem(width: usize, height: usize, elevation: f32, cell_size: f32) -> Dem {
+        Dem {
+            width,
+            height,
+            cell_size,
+            xllcorner: 0.0,
+            yllcorner: 0.0,
+            nodata: -99999.0,
+            elevations: vec![elevation; width * height],
+            has_georeference: true,
+        }
+    }
+
+    #[test]
+    fn raycast_hits_a_flat_mesh_straight_down() {
+        let dem = flat_dem(2, 2, 42.0, 1.0);
+        let heightmap = Heightmap::build(&dem);
+
+        let origin = Vec3::new(0.8, 1000.0, 0.9);
+        let (point, elevation) = heightmap
+            .raycast(origin, Vec3::new(0.0, -1.0, 0.0))
+            .expect("a straight-down ray over the mesh should hit");
+
+        assert!((elevation - 42.0).abs() < 1e-4);
+        assert!((point.x - 0.8).abs() < 1e-4);
+        assert!((point.z - 0.9).abs() < 1e-4);
+    }
+
+    #[test]
+    fn raycast_misses_outside_the_mesh_bounds() {
+        let dem = flat_dem(2, 2, 42.0, 1.0);
+        let heightmap = Heightmap::build(&dem);
+
+        let origin = Vec3::new(50.0, 1000.0, 50.0);
+        assert!(heightmap.raycast(origin, Vec3::new(0.0, -1.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn build_on_a_too_small_dem_yields_an_empty_mesh_that_never_panics() {
+        let dem = flat_dem(1, 1, 10.0, 1.0);
+        let heightmap = Heightmap::build(&dem);
+
+        let origin = Vec3::new(0.0, 1000.0, 0.0);
+        assert!(heightmap.raycast(origin, Vec3::new(0.0, -1.0, 0.0)).is_none());
+    }
+}
@@ -0,0 +1,63 @@
+use crate::dem::Dem;
+use crate::RenderedImage;
+use anyhow::Result;
+use std::path::Path;
+
+/// Writes a rendered image to `output_path` (format inferred from its
+/// extension by the `image` crate) and, alongside it, a world file carrying
+/// the DEM's georeferencing so the result drops back into GIS software at the
+/// right place on the ground.
+pub fn write_output(dem: &Dem, rendered: &RenderedImage, output_path: &str) -> Result<()> {
+    match rendered {
+        RenderedImage::Gray(data) => {
+            let img = image::GrayImage::from_raw(dem.width as u32, dem.height as u32, data.clone())
+                .ok_or_else(|| anyhow::anyhow!("rendered buffer does not match DEM dimensions"))?;
+            img.save(output_path)?;
+        }
+        RenderedImage::Rgb(data) => {
+            let img = image::RgbImage::from_raw(dem.width as u32, dem.height as u32, data.clone())
+                .ok_or_else(|| anyhow::anyhow!("rendered buffer does not match DEM dimensions"))?;
+            img.save(output_path)?;
+        }
+    }
+
+    write_world_file(dem, output_path)
+}
+
+/// Writes a world file (e.g. `.pgw` next to a `.png`) describing the affine
+/// transform from pixel space to the DEM's world coordinates. Skipped when
+/// the source never actually carried georeferencing (e.g. a GridFloat header
+/// missing `xllcorner`/`yllcorner`/`cellsize`), since writing fabricated
+/// 0/0/1 coordinates would be worse than omitting the file.
+fn write_world_file(dem: &Dem, output_path: &str) -> Result<()> {
+    if !dem.has_georeference {
+        return Ok(());
+    }
+
+    let path = Path::new(output_path);
+    let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+        return Ok(());
+    };
+    if extension.len() < 2 {
+        return Ok(());
+    }
+
+    // World-file extension convention: first and last letter of the image
+    // extension plus a trailing `w` (e.g. `png` -> `pgw`, `tif` -> `tfw`).
+    let first = &extension[..1];
+    let last = &extension[extension.len() - 1..];
+    let world_path = path.with_extension(format!("{first}{last}w"));
+
+    // Cell (0, 0) is the north/top row of the grid; `world_xy` already
+    // returns the world coordinate of that pixel's center.
+    let (upper_left_x, upper_left_y) = dem.world_xy(0, 0);
+
+    let contents = format!(
+        "{cell_size}\n0.0\n0.0\n{neg_cell_size}\n{upper_left_x}\n{upper_left_y}\n",
+        cell_size = dem.cell_size,
+        neg_cell_size = -dem.cell_size,
+    );
+    std::fs::write(world_path, contents)?;
+
+    Ok(())
+}
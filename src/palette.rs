@@ -0,0 +1,140 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+/// Fixed color used for any elevation at or below the configured sea level,
+/// so coastal DEMs read correctly even when their local min/max doesn't
+/// actually dip to true sea level.
+const WATER_COLOR: [u8; 3] = [40, 80, 160];
+
+/// Classic hypsometric tint stops: green lowlands shading through tan and
+/// brown into white peaks. Elevations are absolute meters, not normalized to
+/// the DEM's min/max, so two adjacent tiles with different local ranges
+/// still share one consistent color scale.
+const HYPSOMETRIC_STOPS: [(f32, [u8; 3]); 5] = [
+    (0.0, [0, 97, 71]),
+    (300.0, [112, 168, 0]),
+    (900.0, [216, 190, 85]),
+    (1800.0, [140, 90, 55]),
+    (3000.0, [255, 255, 255]),
+];
+
+/// Maps absolute elevations in meters to RGB colors by linear interpolation
+/// between stops, with a fixed water color at or below `sea_level`.
+pub struct Palette {
+    stops: Vec<(f32, [u8; 3])>,
+    sea_level: f32,
+}
+
+impl Palette {
+    /// Resolves `--palette`: a built-in name (currently just `"hypsometric"`)
+    /// or a path to a custom stops file.
+    pub fn resolve(name_or_path: &str, sea_level: f32) -> anyhow::Result<Palette> {
+        let stops = match name_or_path {
+            "hypsometric" => HYPSOMETRIC_STOPS.to_vec(),
+            path => read_stops_file(path)?,
+        };
+        Palette::from_stops(stops, sea_level)
+    }
+
+    fn from_stops(mut stops: Vec<(f32, [u8; 3])>, sea_level: f32) -> anyhow::Result<Palette> {
+        if stops.is_empty() {
+            anyhow::bail!("palette must have at least one elevation stop");
+        }
+        stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        Ok(Palette { stops, sea_level })
+    }
+
+    /// Returns the color for a given elevation, substituting the fixed water
+    /// color at or below sea level.
+    pub fn color_at(&self, elevation: f32) -> [u8; 3] {
+        if elevation <= self.sea_level {
+            return WATER_COLOR;
+        }
+
+        if elevation <= self.stops[0].0 {
+            return self.stops[0].1;
+        }
+
+        for window in self.stops.windows(2) {
+            let (lo_elevation, lo_color) = window[0];
+            let (hi_elevation, hi_color) = window[1];
+            if elevation <= hi_elevation {
+                let t = (elevation - lo_elevation) / (hi_elevation - lo_elevation);
+                return lerp_color(lo_color, hi_color, t);
+            }
+        }
+
+        self.stops.last().unwrap().1
+    }
+}
+
+fn lerp_color(a: [u8; 3], b: [u8; 3], t: f32) -> [u8; 3] {
+    [
+        (a[0] as f32 + (b[0] as f32 - a[0] as f32) * t) as u8,
+        (a[1] as f32 + (b[1] as f32 - a[1] as f32) * t) as u8,
+        (a[2] as f32 + (b[2] as f32 - a[2] as f32) * t) as u8,
+    ]
+}
+
+/// Reads a custom stops file: each non-empty, non-comment (`#`) line holds
+/// `elevation r g b`, e.g. `1800 140 90 55`.
+fn read_stops_file(path: &str) -> anyhow::Result<Vec<(f32, [u8; 3])>> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+
+    let mut stops = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [elevation, r, g, b] = fields.as_slice() else {
+            anyhow::bail!("malformed palette stop line: {line:?} (expected `elevation r g b`)");
+        };
+
+        stops.push((
+            elevation.parse()?,
+            [r.parse()?, g.parse()?, b.parse()?],
+        ));
+    }
+
+    Ok(stops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_at_returns_exact_stop_colors() {
+        let palette = Palette::from_stops(vec![(0.0, [0, 0, 0]), (100.0, [255, 255, 255])], -1.0).unwrap();
+        assert_eq!(palette.color_at(0.0), [0, 0, 0]);
+        assert_eq!(palette.color_at(100.0), [255, 255, 255]);
+    }
+
+    #[test]
+    fn color_at_interpolates_linearly_between_stops() {
+        let palette = Palette::from_stops(vec![(0.0, [0, 0, 0]), (100.0, [200, 100, 0])], -1.0).unwrap();
+        assert_eq!(palette.color_at(50.0), [100, 50, 0]);
+    }
+
+    #[test]
+    fn color_at_clamps_outside_the_stop_range() {
+        let palette = Palette::from_stops(vec![(100.0, [10, 20, 30]), (200.0, [40, 50, 60])], -1.0).unwrap();
+        assert_eq!(palette.color_at(0.0), [10, 20, 30]);
+        assert_eq!(palette.color_at(1000.0), [40, 50, 60]);
+    }
+
+    #[test]
+    fn color_at_returns_water_color_at_or_below_sea_level() {
+        let palette = Palette::from_stops(vec![(-100.0, [0, 100, 0]), (100.0, [200, 200, 200])], 10.0).unwrap();
+        assert_eq!(palette.color_at(10.0), WATER_COLOR);
+        assert_eq!(palette.color_at(-50.0), WATER_COLOR);
+        assert_ne!(palette.color_at(15.0), WATER_COLOR);
+    }
+}